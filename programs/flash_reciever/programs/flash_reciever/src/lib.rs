@@ -1,19 +1,344 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::invoke;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_spl::token::{Token, TokenAccount};
 
 declare_id!("FLASHRcvr1111111111111111111111111111111111");
 
+/// Known DEX program ids the executor is allowed to CPI into. Pinning
+/// these closes off substituting an arbitrary program at the `orca_program`
+/// / `raydium_program` account slots.
+pub const ORCA_WHIRLPOOL_PROGRAM_ID: Pubkey = pubkey!("whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc");
+pub const RAYDIUM_AMM_PROGRAM_ID: Pubkey = pubkey!("675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8");
+
+/// Which pool to hit first; the second leg trades back through the other
+/// DEX to close the loop.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum RouteDirection {
+    OrcaThenRaydium,
+    RaydiumThenOrca,
+}
+
 #[program]
 pub mod flash_receiver {
     use super::*;
-    pub fn execute_flash_arbitrage(ctx: Context<ExecuteFlash>, _bump: u8) -> Result<()> {
-        msg!("flash_receiver: invoked - placeholder");
+
+    /// Execute both legs of the arbitrage atomically: swap `amount_in` into
+    /// the first DEX, swap the proceeds through the second, then assert the
+    /// ending balance cleared `min_profit` over the starting balance. Any
+    /// leg failing, or the profit check failing, reverts the whole
+    /// transaction so an unprofitable route never lands on-chain.
+    pub fn execute_flash_arbitrage(
+        mut ctx: Context<ExecuteFlash>,
+        amount_in: u64,
+        direction: RouteDirection,
+        min_profit: u64,
+    ) -> Result<()> {
+        let starting_balance = ctx.accounts.user_base_token.amount;
+
+        let (first_leg, second_leg): (Leg, Leg) = match direction {
+            RouteDirection::OrcaThenRaydium => (Leg::Orca, Leg::Raydium),
+            RouteDirection::RaydiumThenOrca => (Leg::Raydium, Leg::Orca),
+        };
+
+        // Leg 1 always swaps base -> quote, leg 2 always swaps the quote
+        // proceeds back to base; which DEX services which leg is what
+        // `direction` picks. Each leg's output is the user token account's
+        // *actual* post-CPI balance delta, not the requested input amount,
+        // so leg 2 is genuinely chained off what leg 1 produced.
+        let mid_amount = swap_leg(&mut ctx, first_leg, true, amount_in)?;
+        require!(mid_amount > 0, FlashArbError::UnprofitableRoute);
+        let _ending_amount = swap_leg(&mut ctx, second_leg, false, mid_amount)?;
+
+        let ending_balance = ctx.accounts.user_base_token.amount;
+        let profit = ending_balance.saturating_sub(starting_balance);
+        require!(profit >= min_profit, FlashArbError::UnprofitableRoute);
+
+        msg!("flash_receiver: arb closed, profit={}", profit);
         Ok(())
     }
 }
 
+#[derive(Clone, Copy)]
+enum Leg {
+    Orca,
+    Raydium,
+}
+
+/// Run one leg of the route via CPI into the relevant DEX program and
+/// return the output token account's real post-CPI balance delta, so the
+/// caller can chain it as the next leg's input.
+fn swap_leg(ctx: &mut Context<ExecuteFlash>, leg: Leg, input_is_base: bool, amount_in: u64) -> Result<u64> {
+    match leg {
+        Leg::Orca => invoke_orca_swap(ctx, input_is_base, amount_in),
+        Leg::Raydium => invoke_raydium_swap(ctx, input_is_base, amount_in),
+    }
+}
+
+fn invoke_orca_swap(ctx: &mut Context<ExecuteFlash>, input_is_base: bool, amount_in: u64) -> Result<u64> {
+    let accounts = &ctx.accounts;
+    let starting_dest_balance =
+        if input_is_base { accounts.user_quote_token.amount } else { accounts.user_base_token.amount };
+
+    // Whirlpool's `swap` always takes "token owner account A"/"B" (one per
+    // pool mint) plus an `a_to_b` flag, not a generic source/dest pair, so
+    // figure out which of the user's two accounts is which by comparing
+    // mints against what's actually embedded in the pool account — the
+    // client only ever tells us which leg is "base"/"quote" in *our*
+    // accounting, not Whirlpool's.
+    let (mint_a, vault_a, mint_b, vault_b) = {
+        let pool_data = accounts.orca_pool.try_borrow_data()?;
+        (
+            read_pubkey_at(&pool_data, WHIRLPOOL_MINT_A_OFFSET)?,
+            read_pubkey_at(&pool_data, WHIRLPOOL_VAULT_A_OFFSET)?,
+            read_pubkey_at(&pool_data, WHIRLPOOL_MINT_B_OFFSET)?,
+            read_pubkey_at(&pool_data, WHIRLPOOL_VAULT_B_OFFSET)?,
+        )
+    };
+    require_keys_eq!(accounts.orca_vault_a.key(), vault_a, FlashArbError::VaultMismatch);
+    require_keys_eq!(accounts.orca_vault_b.key(), vault_b, FlashArbError::VaultMismatch);
+
+    let source_mint = if input_is_base { accounts.user_base_token.mint } else { accounts.user_quote_token.mint };
+    let a_to_b = if source_mint == mint_a {
+        true
+    } else if source_mint == mint_b {
+        false
+    } else {
+        return err!(FlashArbError::MintMismatch);
+    };
+    let (token_owner_account_a, token_owner_account_b) = if accounts.user_base_token.mint == mint_a {
+        require_keys_eq!(accounts.user_quote_token.mint, mint_b, FlashArbError::MintMismatch);
+        (accounts.user_base_token.to_account_info(), accounts.user_quote_token.to_account_info())
+    } else {
+        require_keys_eq!(accounts.user_base_token.mint, mint_b, FlashArbError::MintMismatch);
+        require_keys_eq!(accounts.user_quote_token.mint, mint_a, FlashArbError::MintMismatch);
+        (accounts.user_quote_token.to_account_info(), accounts.user_base_token.to_account_info())
+    };
+
+    let args = OrcaSwapArgs {
+        amount: amount_in,
+        // Per-leg slippage isn't enforced here; the atomic post-trade
+        // `min_profit` check on the whole route is the real backstop.
+        other_amount_threshold: 0,
+        sqrt_price_limit: if a_to_b { MIN_SQRT_PRICE } else { MAX_SQRT_PRICE },
+        amount_specified_is_input: true,
+        a_to_b,
+    };
+    let mut data = ORCA_SWAP_DISCRIMINATOR.to_vec();
+    args.serialize(&mut data).map_err(|_| error!(FlashArbError::SwapFailed))?;
+
+    let ix = Instruction {
+        program_id: accounts.orca_program.key(),
+        accounts: vec![
+            AccountMeta::new_readonly(accounts.token_program.key(), false),
+            AccountMeta::new_readonly(accounts.user_account.key(), true),
+            AccountMeta::new(accounts.orca_pool.key(), false),
+            AccountMeta::new(token_owner_account_a.key(), false),
+            AccountMeta::new(accounts.orca_vault_a.key(), false),
+            AccountMeta::new(token_owner_account_b.key(), false),
+            AccountMeta::new(accounts.orca_vault_b.key(), false),
+            AccountMeta::new(accounts.orca_tick_array_0.key(), false),
+            AccountMeta::new(accounts.orca_tick_array_1.key(), false),
+            AccountMeta::new(accounts.orca_tick_array_2.key(), false),
+            AccountMeta::new(accounts.orca_oracle.key(), false),
+        ],
+        data,
+    };
+
+    invoke(
+        &ix,
+        &[
+            accounts.token_program.to_account_info(),
+            accounts.user_account.to_account_info(),
+            accounts.orca_pool.to_account_info(),
+            token_owner_account_a,
+            accounts.orca_vault_a.to_account_info(),
+            token_owner_account_b,
+            accounts.orca_vault_b.to_account_info(),
+            accounts.orca_tick_array_0.to_account_info(),
+            accounts.orca_tick_array_1.to_account_info(),
+            accounts.orca_tick_array_2.to_account_info(),
+            accounts.orca_oracle.to_account_info(),
+        ],
+    )
+    .map_err(|_| error!(FlashArbError::SwapFailed))?;
+
+    read_dest_balance_delta(ctx, input_is_base, starting_dest_balance)
+}
+
+/// Read a `Pubkey` out of raw account `data` at `offset`, erroring instead
+/// of panicking if the account is too short to actually contain it.
+fn read_pubkey_at(data: &[u8], offset: usize) -> Result<Pubkey> {
+    let end = offset.checked_add(32).ok_or_else(|| error!(FlashArbError::SwapFailed))?;
+    let bytes: [u8; 32] = data
+        .get(offset..end)
+        .ok_or_else(|| error!(FlashArbError::SwapFailed))?
+        .try_into()
+        .map_err(|_| error!(FlashArbError::SwapFailed))?;
+    Ok(Pubkey::from(bytes))
+}
+
+fn invoke_raydium_swap(ctx: &mut Context<ExecuteFlash>, input_is_base: bool, amount_in: u64) -> Result<u64> {
+    let accounts = &ctx.accounts;
+    let (user_source, user_dest) = if input_is_base {
+        (accounts.user_base_token.to_account_info(), accounts.user_quote_token.to_account_info())
+    } else {
+        (accounts.user_quote_token.to_account_info(), accounts.user_base_token.to_account_info())
+    };
+    let starting_dest_balance =
+        if input_is_base { accounts.user_quote_token.amount } else { accounts.user_base_token.amount };
+
+    let ix = Instruction {
+        program_id: accounts.raydium_program.key(),
+        accounts: vec![
+            AccountMeta::new(accounts.raydium_pool.key(), false),
+            AccountMeta::new(accounts.raydium_vault_a.key(), false),
+            AccountMeta::new(accounts.raydium_vault_b.key(), false),
+            AccountMeta::new(user_source.key(), false),
+            AccountMeta::new(user_dest.key(), false),
+            AccountMeta::new_readonly(accounts.user_account.key(), true),
+            AccountMeta::new_readonly(accounts.token_program.key(), false),
+        ],
+        data: raydium_swap_ix_data(amount_in),
+    };
+
+    invoke(
+        &ix,
+        &[
+            accounts.raydium_pool.to_account_info(),
+            accounts.raydium_vault_a.to_account_info(),
+            accounts.raydium_vault_b.to_account_info(),
+            user_source,
+            user_dest,
+            accounts.user_account.to_account_info(),
+            accounts.token_program.to_account_info(),
+        ],
+    )
+    .map_err(|_| error!(FlashArbError::SwapFailed))?;
+
+    read_dest_balance_delta(ctx, input_is_base, starting_dest_balance)
+}
+
+/// Reload the leg's destination token account and return how much it
+/// actually gained, rather than trusting the CPI's reported amount.
+fn read_dest_balance_delta(ctx: &mut Context<ExecuteFlash>, input_is_base: bool, starting_dest_balance: u64) -> Result<u64> {
+    if input_is_base {
+        ctx.accounts.user_quote_token.reload()?;
+        Ok(ctx.accounts.user_quote_token.amount.saturating_sub(starting_dest_balance))
+    } else {
+        ctx.accounts.user_base_token.reload()?;
+        Ok(ctx.accounts.user_base_token.amount.saturating_sub(starting_dest_balance))
+    }
+}
+
+/// Whirlpool is an Anchor program, so its instructions are selected by the
+/// 8-byte Anchor sighash `sha256("global:<name>")[..8]`, not a single byte.
+const ORCA_SWAP_DISCRIMINATOR: [u8; 8] = [0xf8, 0xc6, 0x9e, 0x91, 0xe1, 0x75, 0x87, 0xc8];
+const RAYDIUM_SWAP_DISCRIMINATOR: u8 = 0x09;
+
+/// Offsets of `token_mint_a`/`token_vault_a`/`token_mint_b`/`token_vault_b`
+/// within a Whirlpool account, continuing on from
+/// `pool_decode::WHIRLPOOL_SQRT_PRICE_OFFSET` in the off-chain monitor:
+/// `sqrt_price: u128` (65..81), `tick_current_index: i32` (81..85),
+/// `protocol_fee_owed_a: u64` (85..93), `protocol_fee_owed_b: u64` (93..101).
+const WHIRLPOOL_MINT_A_OFFSET: usize = 101;
+const WHIRLPOOL_VAULT_A_OFFSET: usize = 133;
+const WHIRLPOOL_MINT_B_OFFSET: usize = 181;
+const WHIRLPOOL_VAULT_B_OFFSET: usize = 213;
+
+/// Whirlpool rejects a swap whose `sqrt_price_limit` it would cross without
+/// hitting, so a swap that isn't meant to be limited at all still has to
+/// pass the extreme value in the direction it's trading: the global min for
+/// an `a_to_b` swap (price falling), the global max otherwise.
+const MIN_SQRT_PRICE: u128 = 4_295_048_016;
+const MAX_SQRT_PRICE: u128 = 79_226_673_515_401_279_992_447_579_055;
+
+/// Matches Whirlpool's `SwapArgs`: `amount`, `other_amount_threshold`,
+/// `sqrt_price_limit`, `amount_specified_is_input`, `a_to_b`.
+#[derive(AnchorSerialize)]
+struct OrcaSwapArgs {
+    amount: u64,
+    other_amount_threshold: u64,
+    sqrt_price_limit: u128,
+    amount_specified_is_input: bool,
+    a_to_b: bool,
+}
+
+fn raydium_swap_ix_data(amount_in: u64) -> Vec<u8> {
+    let mut data = vec![RAYDIUM_SWAP_DISCRIMINATOR];
+    data.extend_from_slice(&amount_in.to_le_bytes());
+    data
+}
+
 #[derive(Accounts)]
 pub struct ExecuteFlash<'info> {
     #[account(mut)]
     pub user_account: Signer<'info>,
+
+    #[account(mut)]
+    pub user_base_token: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub user_quote_token: Account<'info, TokenAccount>,
+
+    /// CHECK: owner-constrained to the real Whirlpool program so a forged
+    /// pool account can't be substituted here.
+    #[account(mut, owner = ORCA_WHIRLPOOL_PROGRAM_ID @ FlashArbError::UnknownDexProgram)]
+    pub orca_pool: AccountInfo<'info>,
+    /// CHECK: Orca pool token vault; checked against `orca_pool`'s embedded
+    /// `token_vault_a` in `invoke_orca_swap` before the CPI.
+    #[account(mut)]
+    pub orca_vault_a: AccountInfo<'info>,
+    /// CHECK: Orca pool token vault; checked against `orca_pool`'s embedded
+    /// `token_vault_b` in `invoke_orca_swap` before the CPI.
+    #[account(mut)]
+    pub orca_vault_b: AccountInfo<'info>,
+    /// CHECK: one of the three tick arrays straddling the pool's current
+    /// price, computed off-chain by the caller; validated by the Orca
+    /// program during CPI.
+    #[account(mut)]
+    pub orca_tick_array_0: AccountInfo<'info>,
+    /// CHECK: see `orca_tick_array_0`.
+    #[account(mut)]
+    pub orca_tick_array_1: AccountInfo<'info>,
+    /// CHECK: see `orca_tick_array_0`.
+    #[account(mut)]
+    pub orca_tick_array_2: AccountInfo<'info>,
+    /// CHECK: Whirlpool's per-pool oracle PDA, validated by the Orca
+    /// program during CPI.
+    #[account(mut)]
+    pub orca_oracle: AccountInfo<'info>,
+    /// CHECK: pinned to the real Whirlpool program id, invoked directly via CPI.
+    #[account(address = ORCA_WHIRLPOOL_PROGRAM_ID @ FlashArbError::UnknownDexProgram)]
+    pub orca_program: AccountInfo<'info>,
+
+    /// CHECK: owner-constrained to the real Raydium AMM program so a forged
+    /// pool account can't be substituted here.
+    #[account(mut, owner = RAYDIUM_AMM_PROGRAM_ID @ FlashArbError::UnknownDexProgram)]
+    pub raydium_pool: AccountInfo<'info>,
+    /// CHECK: Raydium pool token vault, validated by the Raydium program during CPI.
+    #[account(mut)]
+    pub raydium_vault_a: AccountInfo<'info>,
+    /// CHECK: Raydium pool token vault, validated by the Raydium program during CPI.
+    #[account(mut)]
+    pub raydium_vault_b: AccountInfo<'info>,
+    /// CHECK: pinned to the real Raydium AMM program id, invoked directly via CPI.
+    #[account(address = RAYDIUM_AMM_PROGRAM_ID @ FlashArbError::UnknownDexProgram)]
+    pub raydium_program: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
 }
+
+#[error_code]
+pub enum FlashArbError {
+    #[msg("a swap leg failed")]
+    SwapFailed,
+    #[msg("route did not clear the minimum profit threshold")]
+    UnprofitableRoute,
+    #[msg("pool or program account is not owned by a known DEX program")]
+    UnknownDexProgram,
+    #[msg("provided vault account does not match the one embedded in the pool")]
+    VaultMismatch,
+    #[msg("user token account mint doesn't match either of the pool's mints")]
+    MintMismatch,
+}
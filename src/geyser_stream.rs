@@ -0,0 +1,243 @@
+//! Resilient Geyser account subscriptions.
+//!
+//! `GeyserClient::subscribe_account` hands back a single stream that dies the
+//! moment the gRPC connection drops (network blip, Helius restart, etc). This
+//! module wraps it in a reconnect loop, modeled on the
+//! `geyser-grpc-connector` reconnecting-stream pattern: on any error or EOF we
+//! back off, redial, and re-send the account filter so callers see one
+//! continuous `Stream` regardless of how many times the underlying
+//! connection had to be rebuilt.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Duration;
+
+use async_stream::stream;
+use solana_sdk::pubkey::Pubkey;
+use tokio::time::timeout;
+use tokio_stream::{Stream, StreamExt};
+use yellowstone_grpc_client::{GeyserClient, Interceptor};
+use yellowstone_grpc_proto::geyser::SubscribeUpdateAccount as Message;
+
+/// Per-stage timeouts applied while (re)establishing a Geyser subscription.
+#[derive(Clone, Copy, Debug)]
+pub struct GrpcConnectionTimeouts {
+    pub connect_timeout: Duration,
+    pub request_timeout: Duration,
+    pub subscribe_timeout: Duration,
+    pub receive_timeout: Duration,
+}
+
+impl Default for GrpcConnectionTimeouts {
+    fn default() -> Self {
+        Self {
+            connect_timeout: Duration::from_secs(10),
+            request_timeout: Duration::from_secs(10),
+            subscribe_timeout: Duration::from_secs(10),
+            receive_timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+/// Everything needed to dial (and redial) a single Geyser endpoint.
+#[derive(Clone, Debug)]
+pub struct GrpcSourceConfig {
+    pub endpoint: String,
+    pub x_token: Option<String>,
+    pub timeouts: GrpcConnectionTimeouts,
+}
+
+impl GrpcSourceConfig {
+    pub fn new(endpoint: String, x_token: Option<String>, timeouts: GrpcConnectionTimeouts) -> Self {
+        Self { endpoint, x_token, timeouts }
+    }
+}
+
+/// Base backoff between reconnect attempts; doubles up to `MAX_BACKOFF`.
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+const MAX_BACKOFF: Duration = Duration::from_secs(15);
+
+/// Subscribe to `pubkeys` on `config.endpoint`, yielding account updates
+/// forever. Any connect/subscribe/recv error (including a clean EOF) tears
+/// down the session and redials with exponential backoff, re-sending the
+/// account filter so the caller never has to notice the reconnect.
+pub fn create_reconnecting_account_stream(
+    config: GrpcSourceConfig,
+    pubkeys: Vec<Pubkey>,
+) -> impl Stream<Item = Message> {
+    let request_timeout = config.timeouts.request_timeout;
+    reconnecting_stream(config, "account", move |client| {
+        let pubkeys = pubkeys.clone();
+        async move { subscribe_accounts(client, &pubkeys, request_timeout).await }
+    })
+}
+
+/// Subscribe to slot updates on `config.endpoint`, yielding the current
+/// slot number forever. Reconnects with the same backoff as
+/// [`create_reconnecting_account_stream`]. Useful on its own as a cheap
+/// liveness probe: a Geyser endpoint that can't deliver a slot update is
+/// not one the spread loop should trust.
+pub fn create_reconnecting_slot_stream(config: GrpcSourceConfig) -> impl Stream<Item = u64> {
+    reconnecting_stream(config, "slot", |mut client: GeyserClient<Interceptor>| async move {
+        let stream = client.subscribe_slots().await?;
+        Ok(Box::pin(stream.map(|update| update.slot)) as BoxedStream<u64>)
+    })
+}
+
+/// A boxed, pinned stream — `Box::pin` makes any underlying stream `Unpin`
+/// regardless of what it closes over, which is what lets [`reconnecting_stream`]
+/// stay generic over the concrete stream type each `subscribe` callback
+/// produces.
+type BoxedStream<T> = Pin<Box<dyn Stream<Item = T> + Send>>;
+
+/// Drive the connect/subscribe/receive/backoff loop shared by every Geyser
+/// subscription: dial `config.endpoint`, call `subscribe` to get a stream of
+/// `T`, forward its items until it closes/stalls/errors, then redial with
+/// exponential backoff and repeat forever. `label` only affects log lines, so
+/// a hung slot stream and a hung account stream are easy to tell apart.
+/// [`create_reconnecting_account_stream`] and [`create_reconnecting_slot_stream`]
+/// differ only in what they subscribe to and how they map the result into
+/// `T`, which is exactly what `subscribe` captures.
+fn reconnecting_stream<T, S, Sub, Fut>(
+    config: GrpcSourceConfig,
+    label: &'static str,
+    subscribe: Sub,
+) -> impl Stream<Item = T>
+where
+    Sub: Fn(GeyserClient<Interceptor>) -> Fut,
+    Fut: Future<Output = Result<S, Box<dyn std::error::Error>>>,
+    S: Stream<Item = T> + Unpin,
+{
+    stream! {
+        let mut backoff = INITIAL_BACKOFF;
+
+        loop {
+            let connected = timeout(config.timeouts.connect_timeout, connect(&config)).await;
+            let client = match connected {
+                Ok(Ok(client)) => client,
+                Ok(Err(err)) => {
+                    log::warn!("geyser connect to {} failed: {err}", config.endpoint);
+                    backoff_sleep(&mut backoff).await;
+                    continue;
+                }
+                Err(_) => {
+                    log::warn!("geyser connect to {} timed out", config.endpoint);
+                    backoff_sleep(&mut backoff).await;
+                    continue;
+                }
+            };
+
+            let subscribed = timeout(config.timeouts.subscribe_timeout, subscribe(client)).await;
+            let mut item_stream = match subscribed {
+                Ok(Ok(stream)) => stream,
+                Ok(Err(err)) => {
+                    log::warn!("geyser {label} subscribe on {} failed: {err}", config.endpoint);
+                    backoff_sleep(&mut backoff).await;
+                    continue;
+                }
+                Err(_) => {
+                    log::warn!("geyser {label} subscribe on {} timed out", config.endpoint);
+                    backoff_sleep(&mut backoff).await;
+                    continue;
+                }
+            };
+
+            // Connection is live; reset backoff for the next time it drops.
+            backoff = INITIAL_BACKOFF;
+
+            loop {
+                match timeout(config.timeouts.receive_timeout, item_stream.next()).await {
+                    Ok(Some(item)) => yield item,
+                    Ok(None) => {
+                        log::warn!("geyser {label} stream on {} closed, reconnecting", config.endpoint);
+                        break;
+                    }
+                    Err(_) => {
+                        log::warn!("geyser {label} stream on {} stalled, reconnecting", config.endpoint);
+                        break;
+                    }
+                }
+            }
+
+            backoff_sleep(&mut backoff).await;
+        }
+    }
+}
+
+async fn connect(config: &GrpcSourceConfig) -> Result<GeyserClient<Interceptor>, Box<dyn std::error::Error>> {
+    let client = match &config.x_token {
+        Some(token) => GeyserClient::connect_with_token(config.endpoint.clone(), token.clone()).await?,
+        None => GeyserClient::connect(config.endpoint.clone()).await?,
+    };
+    Ok(client)
+}
+
+async fn subscribe_accounts(
+    client: GeyserClient<Interceptor>,
+    pubkeys: &[Pubkey],
+    request_timeout: Duration,
+) -> Result<BoxedStream<Message>, Box<dyn std::error::Error>> {
+    // Re-issue the account filter on every (re)connect so the new session
+    // watches exactly the same accounts as the one it replaced. Issuing the
+    // per-pubkey `subscribe_account` calls one at a time means their
+    // `request_timeout`s stack up, so past a couple dozen pools the whole
+    // sequence blows through the caller's `subscribe_timeout` even though
+    // every individual call is fast — fan them out concurrently instead, so
+    // the wall-clock cost of (re)subscribing stays close to one
+    // `request_timeout` regardless of how many pools are being watched.
+    let subscriptions = futures::future::join_all(pubkeys.iter().map(|pubkey| {
+        let pubkey = *pubkey;
+        let mut client = client.clone();
+        async move {
+            timeout(request_timeout, client.subscribe_account(pubkey, None))
+                .await
+                .map_err(|_| format!("subscribe_account({pubkey}) timed out after {request_timeout:?}"))?
+        }
+    }))
+    .await;
+
+    let mut streams = Vec::with_capacity(subscriptions.len());
+    for subscription in subscriptions {
+        streams.push(subscription?);
+    }
+    Ok(Box::pin(futures::stream::select_all(streams)))
+}
+
+async fn backoff_sleep(backoff: &mut Duration) {
+    tokio::time::sleep(*backoff).await;
+    *backoff = next_backoff(*backoff);
+}
+
+/// Double `current`, capped at `MAX_BACKOFF`. Split out from `backoff_sleep`
+/// so the doubling/cap behavior is testable without waiting on real sleeps.
+fn next_backoff(current: Duration) -> Duration {
+    std::cmp::min(current * 2, MAX_BACKOFF)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_until_capped() {
+        let mut backoff = INITIAL_BACKOFF;
+        backoff = next_backoff(backoff);
+        assert_eq!(backoff, INITIAL_BACKOFF * 2);
+        backoff = next_backoff(backoff);
+        assert_eq!(backoff, INITIAL_BACKOFF * 4);
+        backoff = next_backoff(backoff);
+        backoff = next_backoff(backoff);
+        assert_eq!(backoff, INITIAL_BACKOFF * 16);
+        backoff = next_backoff(backoff);
+        assert_eq!(backoff, MAX_BACKOFF);
+    }
+
+    #[test]
+    fn backoff_never_exceeds_max() {
+        let mut backoff = MAX_BACKOFF;
+        for _ in 0..5 {
+            backoff = next_backoff(backoff);
+        }
+        assert_eq!(backoff, MAX_BACKOFF);
+    }
+}
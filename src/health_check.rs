@@ -0,0 +1,186 @@
+//! Startup health checks.
+//!
+//! Connecting to a dead or half-broken RPC/Geyser endpoint shouldn't be
+//! discovered an hour into a run via a wall of silent logs. Before the
+//! spread loop is armed we run a small battery of probes against each
+//! configured endpoint, modeled on the rpc-node-check-alive pattern, and
+//! refuse to start if any mandatory one fails.
+
+use std::time::{Duration, Instant};
+
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use tokio::time::timeout;
+use tokio_stream::{Stream, StreamExt};
+
+use crate::geyser_stream::{create_reconnecting_slot_stream, GrpcSourceConfig};
+
+/// Result of a single named probe against an endpoint.
+#[derive(Clone, Debug)]
+pub struct CheckResult {
+    pub name: &'static str,
+    pub passed: bool,
+    pub latency: Duration,
+    pub detail: Option<String>,
+}
+
+/// All probe results for one endpoint.
+#[derive(Clone, Debug)]
+pub struct HealthReport {
+    pub endpoint: String,
+    pub checks: Vec<CheckResult>,
+}
+
+impl HealthReport {
+    /// A report is healthy only if every mandatory check passed.
+    pub fn healthy(&self) -> bool {
+        self.checks.iter().all(|c| c.passed)
+    }
+
+    pub fn summary(&self) -> String {
+        let mut out = format!("health report for {}:\n", self.endpoint);
+        for check in &self.checks {
+            let status = if check.passed { "OK" } else { "FAIL" };
+            out.push_str(&format!(
+                "  [{status}] {} ({:?}){}\n",
+                check.name,
+                check.latency,
+                check.detail.as_ref().map(|d| format!(" - {d}")).unwrap_or_default(),
+            ));
+        }
+        out
+    }
+}
+
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+const GEYSER_SLOT_TIMEOUT: Duration = Duration::from_secs(8);
+
+/// Run all preflight probes against `rpc_url`/`grpc_config` and the pool
+/// pubkeys the bot watches. Returns a report the caller can inspect or just
+/// check `.healthy()` on before arming the spread loop.
+pub async fn run_health_check(
+    rpc_url: &str,
+    grpc_config: GrpcSourceConfig,
+    pool_pubkeys: &[Pubkey],
+) -> HealthReport {
+    let client = RpcClient::new(rpc_url.to_string());
+    let slot_stream = create_reconnecting_slot_stream(grpc_config);
+    run_health_check_with(&client, rpc_url, pool_pubkeys, slot_stream).await
+}
+
+/// Same battery of probes as [`run_health_check`], but takes the RPC client
+/// and slot stream directly so tests can supply fakes instead of dialing
+/// real endpoints.
+async fn run_health_check_with(
+    client: &RpcClient,
+    rpc_url: &str,
+    pool_pubkeys: &[Pubkey],
+    slot_stream: impl Stream<Item = u64> + Unpin,
+) -> HealthReport {
+    let mut checks = Vec::new();
+
+    checks.push(check_get_slot(client).await);
+    for pubkey in pool_pubkeys {
+        checks.push(check_get_account_info(client, *pubkey).await);
+    }
+    checks.push(check_geyser_slot_subscription(slot_stream).await);
+    checks.push(check_token_accounts_by_owner(client, pool_pubkeys.first().copied()).await);
+
+    HealthReport { endpoint: rpc_url.to_string(), checks }
+}
+
+async fn timed<T, E>(name: &'static str, fut: impl std::future::Future<Output = Result<T, E>>) -> CheckResult
+where
+    E: std::fmt::Display,
+{
+    let started = Instant::now();
+    match timeout(PROBE_TIMEOUT, fut).await {
+        Ok(Ok(_)) => CheckResult { name, passed: true, latency: started.elapsed(), detail: None },
+        Ok(Err(err)) => {
+            CheckResult { name, passed: false, latency: started.elapsed(), detail: Some(err.to_string()) }
+        }
+        Err(_) => CheckResult {
+            name,
+            passed: false,
+            latency: started.elapsed(),
+            detail: Some("timed out".to_string()),
+        },
+    }
+}
+
+async fn check_get_slot(client: &RpcClient) -> CheckResult {
+    timed("get_slot", client.get_slot()).await
+}
+
+async fn check_get_account_info(client: &RpcClient, pubkey: Pubkey) -> CheckResult {
+    timed("get_account_info", client.get_account(&pubkey)).await
+}
+
+async fn check_token_accounts_by_owner(client: &RpcClient, owner: Option<Pubkey>) -> CheckResult {
+    let Some(owner) = owner else {
+        return CheckResult {
+            name: "get_token_accounts_by_owner",
+            passed: false,
+            latency: Duration::ZERO,
+            detail: Some("no pool pubkey configured to probe".to_string()),
+        };
+    };
+    let filter = solana_client::rpc_request::TokenAccountsFilter::ProgramId(spl_token::id());
+    timed(
+        "get_token_accounts_by_owner",
+        client.get_token_accounts_by_owner(&owner, filter),
+    )
+    .await
+}
+
+async fn check_geyser_slot_subscription(mut stream: impl Stream<Item = u64> + Unpin) -> CheckResult {
+    let name = "geyser_slot_subscription";
+    let started = Instant::now();
+    match timeout(GEYSER_SLOT_TIMEOUT, stream.next()).await {
+        Ok(Some(_slot)) => CheckResult { name, passed: true, latency: started.elapsed(), detail: None },
+        Ok(None) => CheckResult {
+            name,
+            passed: false,
+            latency: started.elapsed(),
+            detail: Some("stream closed before delivering a slot".to_string()),
+        },
+        Err(_) => CheckResult {
+            name,
+            passed: false,
+            latency: started.elapsed(),
+            detail: Some("no slot delivered within timeout".to_string()),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn geyser_slot_check_passes_when_a_slot_arrives() {
+        let fake_slots = tokio_stream::iter(vec![123u64]);
+        let result = check_geyser_slot_subscription(fake_slots).await;
+        assert!(result.passed, "expected a delivered slot to pass the check: {result:?}");
+    }
+
+    #[tokio::test]
+    async fn geyser_slot_check_fails_when_the_stream_is_empty() {
+        let fake_slots = tokio_stream::iter(Vec::<u64>::new());
+        let result = check_geyser_slot_subscription(fake_slots).await;
+        assert!(!result.passed, "an empty stream must never report healthy");
+    }
+
+    #[test]
+    fn report_is_healthy_only_if_every_check_passed() {
+        let passing = CheckResult { name: "a", passed: true, latency: Duration::ZERO, detail: None };
+        let failing = CheckResult { name: "b", passed: false, latency: Duration::ZERO, detail: None };
+
+        let all_pass =
+            HealthReport { endpoint: "test".to_string(), checks: vec![passing.clone(), passing.clone()] };
+        assert!(all_pass.healthy());
+
+        let one_fails = HealthReport { endpoint: "test".to_string(), checks: vec![passing, failing] };
+        assert!(!one_fails.healthy());
+    }
+}
@@ -1,46 +1,113 @@
-use yellowstone_grpc_client::{GeyserClient, Interceptor};
+mod geyser_stream;
+mod health_check;
+mod pool_decode;
+mod pool_discovery;
+mod price_feed;
+
 use solana_sdk::pubkey::Pubkey;
 use serde::Deserialize;
 use std::str::FromStr;
 
-const ORCA_WSOL_USDC: &str = "7qbRF6YsyGuLUVs6Y1q64bnFoQFrmGBp3obRDXU7X6J9"; // Orca wSOL/USDC pool
-const RAYDIUM_WSOL_USDC: &str = "58oQChx4yWmvK6LfBM2H9GcUb9c4HW7cMc6x64q7ahfk"; // Raydium wSOL/USDC pool
+use geyser_stream::{GrpcConnectionTimeouts, GrpcSourceConfig};
+use health_check::run_health_check;
+use pool_decode::{decode_orca, decode_raydium_vaults};
+use pool_discovery::discover_pools;
+use price_feed::{FeedAggregator, FeedAggregatorConfig};
+
+const WSOL_MINT: &str = "So11111111111111111111111111111111111111112";
+const USDC_MINT: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+const WSOL_DECIMALS: u8 = 9;
+const USDC_DECIMALS: u8 = 6;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let helius_url = std::env::var("HELius_RPC").expect("HELius_RPC not set");
-    let client = GeyserClient::connect(helius_url).await?;
-    let orca = Pubkey::from_str(ORCA_WSOL_USDC).unwrap();
-    let ray = Pubkey::from_str(RAYDIUM_WSOL_USDC).unwrap();
-
-    let mut orca_stream = client.subscribe_account(orca, None).await?;
-    let mut ray_stream  = client.subscribe_account(ray, None).await?;
-
-    while let (Some(o), Some(r)) = tokio::join!(orca_stream.next(), ray_stream.next()) {
-        let orca_price = parse_orca_price(&o.data);
-        let ray_price  = parse_raydium_price(&r.data);
-        let spread = (ray_price - orca_price) / orca_price;
-        if spread.abs() > 0.008 {   // 0.8 % net
-            log_arbitrage(orca_price, ray_price, spread).await?;
-        }
+    let ws_url = std::env::var("HELIUS_WS").expect("HELIUS_WS not set");
+    let rpc_url = std::env::var("HELIUS_RPC_HTTP").expect("HELIUS_RPC_HTTP not set");
+    let wsol = Pubkey::from_str(WSOL_MINT).unwrap();
+    let usdc = Pubkey::from_str(USDC_MINT).unwrap();
+
+    let registry = discover_pools(&rpc_url, wsol, usdc).await?;
+    let pairs = registry.cross_dex_pairs(wsol, usdc);
+    let pool_pubkeys = registry.feed_pubkeys();
+    println!("discovered {} pools, {} Orca/Raydium pairs to watch", pool_pubkeys.len(), pairs.len());
+
+    let grpc_config = GrpcSourceConfig::new(helius_url, None, GrpcConnectionTimeouts::default());
+
+    let report = run_health_check(&rpc_url, grpc_config.clone(), &pool_pubkeys).await;
+    print!("{}", report.summary());
+    if !report.healthy() {
+        eprintln!("preflight health check failed for {}, refusing to arm the bot", report.endpoint);
+        std::process::exit(1);
     }
-}
 
-fn parse_orca_price(data: &[u8]) -> f64 {
-    // Orca constant-product pool: price = sqrt(token_B / token_A)
-    let (a, b) = extract_token_amounts(data); // your helper
-    (b as f64 / a as f64).sqrt()
-}
+    let mut feed = FeedAggregator::spawn(
+        grpc_config,
+        ws_url,
+        rpc_url,
+        pool_pubkeys,
+        FeedAggregatorConfig::default(),
+    );
+
+    while feed.recv().await.is_some() {
+        for (orca_pool, ray_pool) in &pairs {
+            let (base_vault, quote_vault) = match (ray_pool.base_vault, ray_pool.quote_vault) {
+                (Some(base_vault), Some(quote_vault)) => (base_vault, quote_vault),
+                _ => continue, // discovery couldn't resolve this Raydium pool's vaults
+            };
+            let (Some(orca_update), Some(ray_base_update), Some(ray_quote_update)) = (
+                feed.cross_validated(orca_pool.pubkey),
+                feed.cross_validated(base_vault),
+                feed.cross_validated(quote_vault),
+            ) else {
+                continue; // not yet confirmed by >= 2 independent sources
+            };
 
-fn parse_raydium_price(data: &[u8]) -> f64 {
-    // Raydium constant-product AMM: price = sqrt(token_B / token_A)
-    let (a, b) = extract_token_amounts(data); // your helper
-    (b as f64 / a as f64).sqrt()
+            let orca_decoded =
+                decode_orca(orca_pool, &orca_update.data, orca_update.slot, wsol, WSOL_DECIMALS, usdc, USDC_DECIMALS);
+            let ray_decoded = decode_raydium_vaults(
+                ray_pool,
+                &ray_base_update.data,
+                &ray_quote_update.data,
+                ray_quote_update.slot,
+                wsol,
+                WSOL_DECIMALS,
+                usdc,
+                USDC_DECIMALS,
+            );
+            let (Ok(orca_price), Ok(ray_price)) = (orca_decoded, ray_decoded) else {
+                continue;
+            };
+
+            let spread = (ray_price.price - orca_price.price) / orca_price.price;
+            if spread.abs() > 0.008 {   // 0.8 % net
+                let orca_latency = feed.latency_summary(orca_pool.pubkey);
+                let ray_latency = feed.latency_summary(base_vault);
+                log_arbitrage(orca_price.price, ray_price.price, spread, &orca_latency, &ray_latency).await?;
+            }
+        }
+    }
+
+    Ok(())
 }
 
-async fn log_arbitrage(orca: f64, ray: f64, spread: f64) -> Result<(), Box<dyn std::error::Error>> {
-    let msg = format!("Arb: Orca={:.6} Ray={:.6} Spread={:.2}%", orca, ray, spread * 100.0);
-    logger::info("{}", msg);
+/// `orca_latency` is the Orca pool's and `ray_latency` the Raydium pool's base
+/// vault's [`FeedAggregator::latency_summary`] at the moment the signal fired:
+/// how long ago each source last reported for that account, freshest first.
+async fn log_arbitrage(
+    orca: f64,
+    ray: f64,
+    spread: f64,
+    orca_latency: &str,
+    ray_latency: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let msg = format!(
+        "Arb: Orca={:.6} ({orca_latency}) Ray={:.6} ({ray_latency}) Spread={:.2}%",
+        orca,
+        ray,
+        spread * 100.0,
+    );
+    log::info!("{msg}");
     // Stage-2: we’ll post to Telegram here
     Ok(())
 }
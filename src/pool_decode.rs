@@ -0,0 +1,274 @@
+//! Pool account decoding.
+//!
+//! `parse_orca_price`/`parse_raydium_price` used to apply the same
+//! `sqrt(b/a)` constant-product formula to both DEXes, but Orca Whirlpools
+//! and Raydium AMM pools don't store prices the same way: a Whirlpool
+//! account holds a Q64.64 fixed-point `sqrt_price`, while a Raydium AMM v4
+//! pool account doesn't track live reserves at all — the fields at fixed
+//! offsets there are swap-volume counters, not a reserve ratio. The actual
+//! balances live on the separate `token_coin`/`token_pc` SPL Token vault
+//! accounts the pool merely references, which `pool_discovery` resolves and
+//! the feed subscribes to directly. This module decodes each layout into a
+//! common normalized [`PoolPrice`] so spreads are computed on like-for-like
+//! units.
+
+use solana_sdk::pubkey::Pubkey;
+
+use crate::pool_discovery::{Dex, PoolInfo};
+
+/// Byte offset of `sqrt_price: u128` within a Whirlpool account, after the
+/// 8-byte discriminator, `whirlpools_config: Pubkey` (32), `whirlpool_bump`
+/// (1), `tick_spacing: u16` (2), `tick_spacing_seed: [u8; 2]` (2),
+/// `fee_rate: u16` (2), `protocol_fee_rate: u16` (2), and `liquidity: u128`
+/// (16).
+const WHIRLPOOL_SQRT_PRICE_OFFSET: usize = 65;
+
+/// Byte offset of the SPL Token `amount: u64` field within a standard token
+/// account, after `mint: Pubkey` (32) and `owner: Pubkey` (32).
+const TOKEN_ACCOUNT_AMOUNT_OFFSET: usize = 64;
+
+/// A pool's price normalized to quote-per-base, independent of which DEX
+/// it came from.
+#[derive(Clone, Copy, Debug)]
+pub struct PoolPrice {
+    pub dex: Dex,
+    pub price: f64,
+    pub slot: u64,
+    pub base_decimals: u8,
+    pub quote_decimals: u8,
+}
+
+#[derive(Debug)]
+pub enum DecodeError {
+    AccountTooShort { expected: usize, actual: usize },
+}
+
+impl std::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DecodeError::AccountTooShort { expected, actual } => {
+                write!(f, "account data too short: expected at least {expected} bytes, got {actual}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// Decode a Whirlpool account's `sqrt_price` into a normalized price:
+/// `price = (sqrt_price / 2^64)^2`, adjusted for the two mints' decimals.
+pub fn decode_whirlpool(
+    data: &[u8],
+    slot: u64,
+    base_decimals: u8,
+    quote_decimals: u8,
+) -> Result<PoolPrice, DecodeError> {
+    let end = WHIRLPOOL_SQRT_PRICE_OFFSET + 16;
+    if data.len() < end {
+        return Err(DecodeError::AccountTooShort { expected: end, actual: data.len() });
+    }
+
+    let sqrt_price = u128::from_le_bytes(data[WHIRLPOOL_SQRT_PRICE_OFFSET..end].try_into().unwrap());
+    let sqrt_price_x64 = sqrt_price as f64 / (1u128 << 64) as f64;
+    let raw_price = sqrt_price_x64 * sqrt_price_x64;
+    let price = adjust_for_decimals(raw_price, base_decimals, quote_decimals);
+
+    Ok(PoolPrice { dex: Dex::Orca, price, slot, base_decimals, quote_decimals })
+}
+
+/// Read an SPL Token account's `amount` field — the live balance backing a
+/// Raydium pool's reserves.
+fn token_account_amount(data: &[u8]) -> Result<u64, DecodeError> {
+    let end = TOKEN_ACCOUNT_AMOUNT_OFFSET + 8;
+    if data.len() < end {
+        return Err(DecodeError::AccountTooShort { expected: end, actual: data.len() });
+    }
+    Ok(u64::from_le_bytes(data[TOKEN_ACCOUNT_AMOUNT_OFFSET..end].try_into().unwrap()))
+}
+
+/// Decode a Raydium pool's base/quote vault token-account balances into a
+/// normalized price: `price = quote_reserve / base_reserve`, adjusted for
+/// the two mints' decimals. `vault_a_data`/`vault_b_data` are the SPL Token
+/// accounts at `pool.base_vault`/`pool.quote_vault` as discovered by
+/// `pool_discovery` — the pool account itself doesn't track live reserves.
+pub fn decode_raydium_vaults(
+    pool: &PoolInfo,
+    vault_a_data: &[u8],
+    vault_b_data: &[u8],
+    slot: u64,
+    base_mint: Pubkey,
+    base_decimals: u8,
+    quote_mint: Pubkey,
+    quote_decimals: u8,
+) -> Result<PoolPrice, DecodeError> {
+    let swapped = pool.token_a_mint == quote_mint && pool.token_b_mint == base_mint;
+
+    let a_reserve = token_account_amount(vault_a_data)?;
+    let b_reserve = token_account_amount(vault_b_data)?;
+    let (base_reserve, quote_reserve) = if swapped { (b_reserve, a_reserve) } else { (a_reserve, b_reserve) };
+
+    let raw_price = quote_reserve as f64 / base_reserve as f64;
+    let price = adjust_for_decimals(raw_price, base_decimals, quote_decimals);
+
+    Ok(PoolPrice { dex: Dex::Raydium, price, slot, base_decimals, quote_decimals })
+}
+
+/// Decode an Orca Whirlpool's account `data` into a [`PoolPrice`] oriented
+/// as `quote_mint`-per-`base_mint`, regardless of which mint `pool_discovery`
+/// actually found sitting at the on-chain "A"/"B" offset for this
+/// particular pool. Whirlpool doesn't guarantee that, so two pools of the
+/// same pair can have it swapped relative to each other — decoding both
+/// with the same hardcoded order silently inverts one of them, making the
+/// two prices off by roughly `(quote/base)^2` instead of comparable. This
+/// reads `pool.token_a_mint`/`token_b_mint` (as recorded by discovery) to
+/// pick the right decimal order and flips the result back to the canonical
+/// orientation when they're swapped.
+pub fn decode_orca(
+    pool: &PoolInfo,
+    data: &[u8],
+    slot: u64,
+    base_mint: Pubkey,
+    base_decimals: u8,
+    quote_mint: Pubkey,
+    quote_decimals: u8,
+) -> Result<PoolPrice, DecodeError> {
+    let swapped = pool.token_a_mint == quote_mint && pool.token_b_mint == base_mint;
+    let (offset_a_decimals, offset_b_decimals) =
+        if swapped { (quote_decimals, base_decimals) } else { (base_decimals, quote_decimals) };
+
+    let raw = decode_whirlpool(data, slot, offset_a_decimals, offset_b_decimals)?;
+
+    Ok(if swapped {
+        PoolPrice { price: 1.0 / raw.price, base_decimals, quote_decimals, ..raw }
+    } else {
+        raw
+    })
+}
+
+/// A raw `quote_atoms / base_atoms` ratio needs to be rescaled by the two
+/// mints' decimal places to read as quote-units-per-base-unit.
+fn adjust_for_decimals(raw_price: f64, base_decimals: u8, quote_decimals: u8) -> f64 {
+    raw_price * 10f64.powi(base_decimals as i32 - quote_decimals as i32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A zero-filled buffer with `value` written little-endian at `offset`,
+    /// sized so every fixed offset this module reads from is in bounds —
+    /// the layout detail that matters for these tests, not a full
+    /// discriminator/field-accurate account.
+    fn fixture(len: usize, offset: usize, value: &[u8]) -> Vec<u8> {
+        let mut data = vec![0u8; len];
+        data[offset..offset + value.len()].copy_from_slice(value);
+        data
+    }
+
+    #[test]
+    fn decode_whirlpool_reads_sqrt_price_regardless_of_which_mint_is_base() {
+        // sqrt_price for a 1:1 pool is exactly 2^64 in Q64.64.
+        let sqrt_price: u128 = 1u128 << 64;
+        let data = fixture(WHIRLPOOL_SQRT_PRICE_OFFSET + 16, WHIRLPOOL_SQRT_PRICE_OFFSET, &sqrt_price.to_le_bytes());
+
+        // The pool itself doesn't know or care which mint the caller calls
+        // "base" vs "quote" — it only stores a byte layout. Decoding reads
+        // the same sqrt_price and applies whichever decimals the caller
+        // passes, in that order; reorienting for a swapped mint assignment
+        // is left to `decode_orca`.
+        let wsol_base = decode_whirlpool(&data, 42, 9, 6).expect("decode should succeed");
+        assert_eq!(wsol_base.price, 1_000.0);
+
+        let usdc_base = decode_whirlpool(&data, 42, 6, 9).expect("decode should succeed");
+        assert_eq!(usdc_base.price, 0.001);
+    }
+
+    #[test]
+    fn decode_whirlpool_rejects_a_short_account() {
+        let data = vec![0u8; WHIRLPOOL_SQRT_PRICE_OFFSET];
+        let err = decode_whirlpool(&data, 1, 9, 6).unwrap_err();
+        assert!(matches!(err, DecodeError::AccountTooShort { .. }));
+    }
+
+    #[test]
+    fn decode_raydium_vaults_reads_reserve_ratio_regardless_of_which_mint_is_base() {
+        let wsol = Pubkey::new_unique();
+        let usdc = Pubkey::new_unique();
+        let base_reserve: u64 = 1_000 * 10u64.pow(9);
+        let quote_reserve: u64 = 1_000_000 * 10u64.pow(6);
+        let vault_a = fixture(TOKEN_ACCOUNT_AMOUNT_OFFSET + 8, TOKEN_ACCOUNT_AMOUNT_OFFSET, &base_reserve.to_le_bytes());
+        let vault_b = fixture(TOKEN_ACCOUNT_AMOUNT_OFFSET + 8, TOKEN_ACCOUNT_AMOUNT_OFFSET, &quote_reserve.to_le_bytes());
+        let pool = raydium_pool(Pubkey::new_unique(), wsol, usdc);
+
+        let decoded =
+            decode_raydium_vaults(&pool, &vault_a, &vault_b, 7, wsol, 9, usdc, 6).expect("decode should succeed");
+        assert_eq!(decoded.price, 1_000.0);
+
+        // USDC recorded as token_a: vault_a now holds the quote reserve.
+        let swapped_quote_reserve: u64 = 2_000_000 * 10u64.pow(6);
+        let usdc_vault =
+            fixture(TOKEN_ACCOUNT_AMOUNT_OFFSET + 8, TOKEN_ACCOUNT_AMOUNT_OFFSET, &swapped_quote_reserve.to_le_bytes());
+        let swapped_pool = raydium_pool(Pubkey::new_unique(), usdc, wsol);
+        let decoded = decode_raydium_vaults(&swapped_pool, &usdc_vault, &vault_a, 7, wsol, 9, usdc, 6)
+            .expect("decode should succeed");
+        assert_eq!(decoded.price, 2_000.0);
+    }
+
+    #[test]
+    fn decode_raydium_vaults_rejects_a_short_account() {
+        let wsol = Pubkey::new_unique();
+        let usdc = Pubkey::new_unique();
+        let pool = raydium_pool(Pubkey::new_unique(), wsol, usdc);
+        let short = vec![0u8; TOKEN_ACCOUNT_AMOUNT_OFFSET];
+        let full = vec![0u8; TOKEN_ACCOUNT_AMOUNT_OFFSET + 8];
+
+        let err = decode_raydium_vaults(&pool, &short, &full, 1, wsol, 9, usdc, 6).unwrap_err();
+        assert!(matches!(err, DecodeError::AccountTooShort { .. }));
+    }
+
+    fn raydium_pool(pubkey: Pubkey, token_a_mint: Pubkey, token_b_mint: Pubkey) -> PoolInfo {
+        PoolInfo {
+            pubkey,
+            dex: Dex::Raydium,
+            token_a_mint,
+            token_b_mint,
+            base_vault: Some(Pubkey::new_unique()),
+            quote_vault: Some(Pubkey::new_unique()),
+        }
+    }
+
+    fn orca_pool(pubkey: Pubkey, token_a_mint: Pubkey, token_b_mint: Pubkey) -> PoolInfo {
+        PoolInfo { pubkey, dex: Dex::Orca, token_a_mint, token_b_mint, base_vault: None, quote_vault: None }
+    }
+
+    #[test]
+    fn decode_orca_matches_wsol_price_regardless_of_which_offset_discovery_found_it_at() {
+        let wsol = Pubkey::new_unique();
+        let usdc = Pubkey::new_unique();
+
+        // sqrt_price for a 1:1 pool is exactly 2^64 in Q64.64.
+        let sqrt_price: u128 = 1u128 << 64;
+        let data = fixture(WHIRLPOOL_SQRT_PRICE_OFFSET + 16, WHIRLPOOL_SQRT_PRICE_OFFSET, &sqrt_price.to_le_bytes());
+
+        // Pool A: discovery found wSOL at offset_a, USDC at offset_b.
+        let pool_a = orca_pool(Pubkey::new_unique(), wsol, usdc);
+        let price_a = decode_orca(&pool_a, &data, 1, wsol, 9, usdc, 6).expect("decode should succeed");
+
+        // Pool B: discovery found the same pair but with USDC at offset_a,
+        // wSOL at offset_b — the swapped assignment `fetch_matching_pools`
+        // exists specifically to also find.
+        let pool_b = orca_pool(Pubkey::new_unique(), usdc, wsol);
+        let price_b = decode_orca(&pool_b, &data, 1, wsol, 9, usdc, 6).expect("decode should succeed");
+
+        // Both pools hold the same sqrt_price in substance, just at a
+        // swapped mint assignment, so `decode_orca` must return the same
+        // USDC-per-wSOL price for both rather than one being the inverse of
+        // the other.
+        assert!(
+            (price_a.price - price_b.price).abs() < 1e-6,
+            "price_a={} price_b={} should match",
+            price_a.price,
+            price_b.price
+        );
+    }
+}
@@ -0,0 +1,296 @@
+//! Dynamic pool discovery.
+//!
+//! `ORCA_WSOL_USDC`/`RAYDIUM_WSOL_USDC` only ever watched one hardcoded
+//! pool per DEX. Instead, enumerate every wSOL/USDC pool on each program via
+//! `getProgramAccounts` with `memcmp` filters on the mint fields (plus a
+//! `dataSize` filter to rule out unrelated account types), and build a
+//! registry mapping each discovered pool to its DEX and token-pair metadata.
+//! The spread loop then scans every Orca-vs-Raydium pair for the pair it
+//! cares about instead of a single fixed pool, so new pools show up without
+//! a code change.
+//!
+//! Neither program guarantees which mint sits at the "A" offset and which
+//! sits at "B" — Whirlpool in particular orders `token_mint_a < token_mint_b`
+//! by pubkey, so wSOL isn't reliably the first mint. `fetch_matching_pools`
+//! therefore tries both assignments of the two mints to the two offsets
+//! rather than assuming a fixed order.
+
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use solana_client::rpc_filter::{Memcmp, MemcmpEncodedBytes, RpcFilterType};
+use solana_sdk::pubkey::Pubkey;
+
+pub const ORCA_WHIRLPOOL_PROGRAM: &str = "whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc";
+pub const RAYDIUM_AMM_PROGRAM: &str = "675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8";
+
+/// Byte offsets of the token-A/token-B mint fields within each program's
+/// pool account layout, used to build the `memcmp` discovery filters.
+const WHIRLPOOL_MINT_A_OFFSET: usize = 101;
+const WHIRLPOOL_MINT_B_OFFSET: usize = 181;
+const WHIRLPOOL_ACCOUNT_SIZE: u64 = 653;
+
+const RAYDIUM_MINT_A_OFFSET: usize = 400;
+const RAYDIUM_MINT_B_OFFSET: usize = 432;
+const RAYDIUM_ACCOUNT_SIZE: u64 = 752;
+
+/// Byte offsets of the `token_coin`/`token_pc` vault pubkeys within a
+/// Raydium AMM v4 pool account — together the 64 bytes immediately before
+/// `coin_mint` at `RAYDIUM_MINT_A_OFFSET`.
+/// The pool account itself doesn't track live reserves; those live on the
+/// separate SPL Token accounts at these pubkeys, which `pool_decode` reads
+/// directly.
+const RAYDIUM_VAULT_A_OFFSET: usize = 336;
+const RAYDIUM_VAULT_B_OFFSET: usize = 368;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Dex {
+    Orca,
+    Raydium,
+}
+
+/// A discovered pool and the token pair it trades. `base_vault`/`quote_vault`
+/// (the SPL Token accounts holding `token_a_mint`'s/`token_b_mint`'s live
+/// balance) are only populated for Raydium — Orca's price comes straight
+/// from the Whirlpool account's `sqrt_price`, so it has none.
+#[derive(Clone, Copy, Debug)]
+pub struct PoolInfo {
+    pub pubkey: Pubkey,
+    pub dex: Dex,
+    pub token_a_mint: Pubkey,
+    pub token_b_mint: Pubkey,
+    pub base_vault: Option<Pubkey>,
+    pub quote_vault: Option<Pubkey>,
+}
+
+/// All pools discovered at startup, keyed by pubkey for O(1) lookup when an
+/// account update comes in off the feed.
+#[derive(Default)]
+pub struct PoolRegistry {
+    pools: HashMap<Pubkey, PoolInfo>,
+}
+
+impl PoolRegistry {
+    pub fn insert(&mut self, info: PoolInfo) {
+        self.pools.insert(info.pubkey, info);
+    }
+
+    pub fn get(&self, pubkey: &Pubkey) -> Option<&PoolInfo> {
+        self.pools.get(pubkey)
+    }
+
+    pub fn pubkeys(&self) -> Vec<Pubkey> {
+        self.pools.keys().copied().collect()
+    }
+
+    /// Every pubkey the price feed needs to subscribe to: each pool account
+    /// plus, for Raydium, its `base_vault`/`quote_vault` — the feed has to
+    /// track those directly since the pool account alone can't tell you the
+    /// current reserves.
+    pub fn feed_pubkeys(&self) -> Vec<Pubkey> {
+        self.pools
+            .values()
+            .flat_map(|p| [Some(p.pubkey), p.base_vault, p.quote_vault])
+            .flatten()
+            .collect()
+    }
+
+    /// Every (Orca pool, Raydium pool) pair trading `token_a`/`token_b`
+    /// (mint order doesn't matter), for the spread loop to scan.
+    pub fn cross_dex_pairs(&self, token_a: Pubkey, token_b: Pubkey) -> Vec<(PoolInfo, PoolInfo)> {
+        let matches_pair = |info: &PoolInfo| {
+            let mints = (info.token_a_mint, info.token_b_mint);
+            mints == (token_a, token_b) || mints == (token_b, token_a)
+        };
+
+        let orca: Vec<PoolInfo> =
+            self.pools.values().filter(|p| p.dex == Dex::Orca && matches_pair(p)).copied().collect();
+        let raydium: Vec<PoolInfo> =
+            self.pools.values().filter(|p| p.dex == Dex::Raydium && matches_pair(p)).copied().collect();
+
+        orca.into_iter()
+            .flat_map(|o| raydium.iter().map(move |r| (o, *r)))
+            .collect()
+    }
+}
+
+/// Discover every wSOL/USDC pool on Orca and Raydium and return a populated
+/// registry. `wsol_mint`/`usdc_mint` are used to build the `memcmp` filters.
+pub async fn discover_pools(
+    rpc_url: &str,
+    wsol_mint: Pubkey,
+    usdc_mint: Pubkey,
+) -> Result<PoolRegistry, Box<dyn std::error::Error>> {
+    let client = RpcClient::new(rpc_url.to_string());
+    let mut registry = PoolRegistry::default();
+
+    let orca_program = Pubkey::from_str(ORCA_WHIRLPOOL_PROGRAM)?;
+    for (pubkey, token_a_mint, token_b_mint) in fetch_matching_pools(
+        &client,
+        orca_program,
+        WHIRLPOOL_MINT_A_OFFSET,
+        WHIRLPOOL_MINT_B_OFFSET,
+        wsol_mint,
+        usdc_mint,
+        WHIRLPOOL_ACCOUNT_SIZE,
+    )
+    .await?
+    {
+        registry.insert(PoolInfo {
+            pubkey,
+            dex: Dex::Orca,
+            token_a_mint,
+            token_b_mint,
+            base_vault: None,
+            quote_vault: None,
+        });
+    }
+
+    let raydium_program = Pubkey::from_str(RAYDIUM_AMM_PROGRAM)?;
+    for (pubkey, token_a_mint, token_b_mint) in fetch_matching_pools(
+        &client,
+        raydium_program,
+        RAYDIUM_MINT_A_OFFSET,
+        RAYDIUM_MINT_B_OFFSET,
+        wsol_mint,
+        usdc_mint,
+        RAYDIUM_ACCOUNT_SIZE,
+    )
+    .await?
+    {
+        let account = client.get_account(&pubkey).await?;
+        let base_vault = read_pubkey(&account.data, RAYDIUM_VAULT_A_OFFSET)?;
+        let quote_vault = read_pubkey(&account.data, RAYDIUM_VAULT_B_OFFSET)?;
+        registry.insert(PoolInfo {
+            pubkey,
+            dex: Dex::Raydium,
+            token_a_mint,
+            token_b_mint,
+            base_vault: Some(base_vault),
+            quote_vault: Some(quote_vault),
+        });
+    }
+
+    Ok(registry)
+}
+
+/// Read a `Pubkey` out of raw account `data` at `offset`.
+fn read_pubkey(data: &[u8], offset: usize) -> Result<Pubkey, Box<dyn std::error::Error>> {
+    let end = offset + 32;
+    let bytes: [u8; 32] = data.get(offset..end).ok_or("account too short to contain a pubkey")?.try_into()?;
+    Ok(Pubkey::new_from_array(bytes))
+}
+
+/// Run `getProgramAccounts` against `program` with a `dataSize` filter plus
+/// `memcmp` matches on both mint fields. The program doesn't guarantee
+/// which mint lives at `offset_a` vs `offset_b`, so this queries both
+/// assignments of `(mint_x, mint_y)` to `(offset_a, offset_b)` and merges
+/// the results, deduplicating by pubkey. Each returned pool carries the
+/// mint assignment that actually matched — (pubkey, mint_at_offset_a,
+/// mint_at_offset_b) — so callers decoding the account know which mint
+/// sits at which offset instead of assuming a fixed order.
+async fn fetch_matching_pools(
+    client: &RpcClient,
+    program: Pubkey,
+    offset_a: usize,
+    offset_b: usize,
+    mint_x: Pubkey,
+    mint_y: Pubkey,
+    account_size: u64,
+) -> Result<Vec<(Pubkey, Pubkey, Pubkey)>, Box<dyn std::error::Error>> {
+    let mut seen = HashSet::new();
+    let mut pools = Vec::new();
+
+    for (a, b) in mint_order_candidates(mint_x, mint_y) {
+        for pubkey in fetch_with_mint_order(client, program, (offset_a, a), (offset_b, b), account_size).await? {
+            if seen.insert(pubkey) {
+                pools.push((pubkey, a, b));
+            }
+        }
+    }
+
+    Ok(pools)
+}
+
+/// Both possible assignments of `(mint_x, mint_y)` to `(offset_a, offset_b)`.
+/// Split out so the "don't assume a fixed order" behavior is testable
+/// without a live RPC endpoint.
+fn mint_order_candidates(mint_x: Pubkey, mint_y: Pubkey) -> [(Pubkey, Pubkey); 2] {
+    [(mint_x, mint_y), (mint_y, mint_x)]
+}
+
+async fn fetch_with_mint_order(
+    client: &RpcClient,
+    program: Pubkey,
+    mint_a: (usize, Pubkey),
+    mint_b: (usize, Pubkey),
+    account_size: u64,
+) -> Result<Vec<Pubkey>, Box<dyn std::error::Error>> {
+    let config = RpcProgramAccountsConfig {
+        filters: Some(vec![
+            RpcFilterType::DataSize(account_size),
+            RpcFilterType::Memcmp(Memcmp::new(mint_a.0, MemcmpEncodedBytes::Base58(mint_a.1.to_string()))),
+            RpcFilterType::Memcmp(Memcmp::new(mint_b.0, MemcmpEncodedBytes::Base58(mint_b.1.to_string()))),
+        ]),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    let accounts = client.get_program_accounts_with_config(&program, config).await?;
+    Ok(accounts.into_iter().map(|(pubkey, _)| pubkey).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mint_order_candidates_cover_both_assignments() {
+        let wsol = Pubkey::from_str("So11111111111111111111111111111111111111112").unwrap();
+        let usdc = Pubkey::from_str("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v").unwrap();
+
+        let candidates = mint_order_candidates(wsol, usdc);
+
+        // Real Whirlpool accounts enforce token_mint_a < token_mint_b, so
+        // whichever of wSOL/USDC sorts first occupies the "A" offset. Both
+        // orderings must be tried, or discovery silently finds nothing for
+        // whichever pair isn't queried.
+        assert!(candidates.contains(&(wsol, usdc)));
+        assert!(candidates.contains(&(usdc, wsol)));
+    }
+
+    #[test]
+    fn cross_dex_pairs_matches_regardless_of_mint_order() {
+        let wsol = Pubkey::from_str("So11111111111111111111111111111111111111112").unwrap();
+        let usdc = Pubkey::from_str("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v").unwrap();
+
+        let mut registry = PoolRegistry::default();
+        // The Orca pool records usdc at the "A" offset and the Raydium pool
+        // records wsol there, so the two disagree on mint order.
+        registry.insert(PoolInfo {
+            pubkey: Pubkey::new_unique(),
+            dex: Dex::Orca,
+            token_a_mint: usdc,
+            token_b_mint: wsol,
+            base_vault: None,
+            quote_vault: None,
+        });
+        registry.insert(PoolInfo {
+            pubkey: Pubkey::new_unique(),
+            dex: Dex::Raydium,
+            token_a_mint: wsol,
+            token_b_mint: usdc,
+            base_vault: Some(Pubkey::new_unique()),
+            quote_vault: Some(Pubkey::new_unique()),
+        });
+
+        let pairs = registry.cross_dex_pairs(wsol, usdc);
+        assert_eq!(pairs.len(), 1, "pools trading the pair in either mint order must still be paired up");
+    }
+}
@@ -0,0 +1,265 @@
+//! Multi-source price feed aggregation.
+//!
+//! A single Geyser account stream can lag or drop silently, and nothing
+//! stops a phantom spread from firing off a stale read. This module fans
+//! the same pool accounts out over three independent transports —
+//! Yellowstone gRPC, a standard `accountSubscribe` WebSocket, and periodic
+//! `getAccountInfo` RPC polling — and funnels every update through one
+//! channel tagged with its source and arrival time. Callers cross-validate
+//! before trusting a reading: the latest update's slot must be confirmed by
+//! at least two independent sources within `slot_tolerance`.
+//!
+//! This mirrors the "fan one subscription across multiple sources and
+//! compare arrival times" approach used by Solana latency testers.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use solana_client::nonblocking::pubsub_client::PubsubClient;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_config::RpcAccountInfoConfig;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use tokio::sync::mpsc;
+use tokio_stream::StreamExt;
+
+use crate::geyser_stream::{create_reconnecting_account_stream, GrpcConnectionTimeouts, GrpcSourceConfig};
+
+/// Which transport a `FeedUpdate` arrived on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FeedSource {
+    Grpc,
+    WebSocket,
+    Rpc,
+}
+
+/// A single account update tagged with where and when it arrived.
+#[derive(Clone, Debug)]
+pub struct FeedUpdate {
+    pub source: FeedSource,
+    pub pool: Pubkey,
+    pub slot: u64,
+    pub data: Vec<u8>,
+    pub arrived_at: Instant,
+}
+
+/// How close together two sources' slots must be to count as agreeing.
+#[derive(Clone, Copy, Debug)]
+pub struct FeedAggregatorConfig {
+    pub slot_tolerance: u64,
+    pub rpc_poll_interval: Duration,
+}
+
+impl Default for FeedAggregatorConfig {
+    fn default() -> Self {
+        Self { slot_tolerance: 2, rpc_poll_interval: Duration::from_millis(400) }
+    }
+}
+
+/// Tracks the most recent update per pool/source pair so a reading can be
+/// cross-validated and the operator can see which endpoint is freshest.
+pub struct FeedAggregator {
+    config: FeedAggregatorConfig,
+    receiver: mpsc::Receiver<FeedUpdate>,
+    last_seen: HashMap<(Pubkey, FeedSource), FeedUpdate>,
+}
+
+impl FeedAggregator {
+    /// Spawn gRPC, WebSocket, and RPC-polling tasks for `pools` and return
+    /// an aggregator that reads their combined output.
+    pub fn spawn(
+        grpc_config: GrpcSourceConfig,
+        ws_url: String,
+        rpc_url: String,
+        pools: Vec<Pubkey>,
+        config: FeedAggregatorConfig,
+    ) -> Self {
+        let (tx, rx) = mpsc::channel(1024);
+
+        tokio::spawn(run_grpc_source(grpc_config, pools.clone(), tx.clone()));
+        tokio::spawn(run_ws_source(ws_url, pools.clone(), tx.clone()));
+        tokio::spawn(run_rpc_source(rpc_url, pools, config.rpc_poll_interval, tx));
+
+        Self { config, receiver: rx, last_seen: HashMap::new() }
+    }
+
+    /// Wait for the next update and record it. Returns `None` once every
+    /// source task has exited (the aggregator is done for good).
+    pub async fn recv(&mut self) -> Option<FeedUpdate> {
+        let update = self.receiver.recv().await?;
+        self.last_seen.insert((update.pool, update.source), update.clone());
+        Some(update)
+    }
+
+    /// Cross-validate `pool`'s latest slot against the other sources' most
+    /// recent readings. Returns the confirmed update only if at least one
+    /// other source reported a slot within `slot_tolerance` of it.
+    pub fn cross_validated(&self, pool: Pubkey) -> Option<&FeedUpdate> {
+        let sources = [FeedSource::Grpc, FeedSource::WebSocket, FeedSource::Rpc];
+        let updates: Vec<&FeedUpdate> =
+            sources.iter().filter_map(|s| self.last_seen.get(&(pool, *s))).collect();
+
+        let latest = updates.iter().max_by_key(|u| u.slot)?;
+        let confirmations = updates
+            .iter()
+            .filter(|u| u.slot.abs_diff(latest.slot) <= self.config.slot_tolerance)
+            .count();
+
+        (confirmations >= 2).then(|| *latest)
+    }
+
+    /// How long ago each source last reported for `pool`, for operator
+    /// visibility into which endpoint is freshest.
+    pub fn source_latencies(&self, pool: Pubkey) -> HashMap<FeedSource, Duration> {
+        [FeedSource::Grpc, FeedSource::WebSocket, FeedSource::Rpc]
+            .into_iter()
+            .filter_map(|source| {
+                let update = self.last_seen.get(&(pool, source))?;
+                Some((source, update.arrived_at.elapsed()))
+            })
+            .collect()
+    }
+
+    /// `source_latencies` rendered freshest-first, e.g. `"Grpc=12ms,
+    /// WebSocket=48ms"`, for dropping straight into an operator-facing log
+    /// line.
+    pub fn latency_summary(&self, pool: Pubkey) -> String {
+        let mut latencies: Vec<(FeedSource, Duration)> = self.source_latencies(pool).into_iter().collect();
+        latencies.sort_by_key(|(_, latency)| *latency);
+        latencies.into_iter().map(|(source, latency)| format!("{source:?}={latency:?}")).collect::<Vec<_>>().join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn aggregator(slot_tolerance: u64) -> FeedAggregator {
+        let (_tx, rx) = mpsc::channel(1);
+        let config = FeedAggregatorConfig { slot_tolerance, rpc_poll_interval: Duration::from_millis(400) };
+        FeedAggregator { config, receiver: rx, last_seen: HashMap::new() }
+    }
+
+    fn update(source: FeedSource, pool: Pubkey, slot: u64) -> FeedUpdate {
+        FeedUpdate { source, pool, slot, data: vec![], arrived_at: Instant::now() }
+    }
+
+    #[test]
+    fn cross_validated_is_none_without_any_updates() {
+        let agg = aggregator(2);
+        assert!(agg.cross_validated(Pubkey::new_unique()).is_none());
+    }
+
+    #[test]
+    fn cross_validated_is_none_with_only_one_source() {
+        let pool = Pubkey::new_unique();
+        let mut agg = aggregator(2);
+        agg.last_seen.insert((pool, FeedSource::Grpc), update(FeedSource::Grpc, pool, 100));
+        assert!(agg.cross_validated(pool).is_none());
+    }
+
+    #[test]
+    fn cross_validated_confirms_when_two_sources_agree_within_tolerance() {
+        let pool = Pubkey::new_unique();
+        let mut agg = aggregator(2);
+        agg.last_seen.insert((pool, FeedSource::Grpc), update(FeedSource::Grpc, pool, 100));
+        agg.last_seen.insert((pool, FeedSource::WebSocket), update(FeedSource::WebSocket, pool, 101));
+
+        let confirmed = agg.cross_validated(pool).expect("two sources within tolerance should confirm");
+        assert_eq!(confirmed.slot, 101);
+    }
+
+    #[test]
+    fn cross_validated_rejects_sources_outside_tolerance() {
+        let pool = Pubkey::new_unique();
+        let mut agg = aggregator(2);
+        agg.last_seen.insert((pool, FeedSource::Grpc), update(FeedSource::Grpc, pool, 100));
+        agg.last_seen.insert((pool, FeedSource::WebSocket), update(FeedSource::WebSocket, pool, 50));
+
+        assert!(agg.cross_validated(pool).is_none());
+    }
+}
+
+async fn run_grpc_source(config: GrpcSourceConfig, pools: Vec<Pubkey>, tx: mpsc::Sender<FeedUpdate>) {
+    let mut stream = create_reconnecting_account_stream(config, pools);
+    while let Some(update) = stream.next().await {
+        let Ok(pool) = Pubkey::try_from(update.pubkey.as_slice()) else { continue };
+        let _ = tx
+            .send(FeedUpdate {
+                source: FeedSource::Grpc,
+                pool,
+                slot: update.slot,
+                data: update.data,
+                arrived_at: Instant::now(),
+            })
+            .await;
+    }
+}
+
+async fn run_ws_source(ws_url: String, pools: Vec<Pubkey>, tx: mpsc::Sender<FeedUpdate>) {
+    let Ok(client) = PubsubClient::new(&ws_url).await else {
+        log::warn!("price_feed: failed to connect websocket source {ws_url}");
+        return;
+    };
+
+    let mut handles = Vec::with_capacity(pools.len());
+    for pool in pools {
+        let Ok((mut stream, _unsubscribe)) = client
+            .account_subscribe(&pool, Some(solana_client::rpc_config::RpcAccountInfoConfig {
+                commitment: Some(CommitmentConfig::confirmed()),
+                ..Default::default()
+            }))
+            .await
+        else {
+            log::warn!("price_feed: websocket account_subscribe for {pool} failed, this pool won't cross-validate via {ws_url}");
+            continue;
+        };
+        let tx = tx.clone();
+        handles.push(tokio::spawn(async move {
+            while let Some(response) = stream.next().await {
+                let slot = response.context.slot;
+                let Some(data) = response.value.data.decode() else { continue };
+                let _ = tx
+                    .send(FeedUpdate { source: FeedSource::WebSocket, pool, slot, data, arrived_at: Instant::now() })
+                    .await;
+            }
+        }));
+    }
+
+    for handle in handles {
+        let _ = handle.await;
+    }
+}
+
+async fn run_rpc_source(
+    rpc_url: String,
+    pools: Vec<Pubkey>,
+    poll_interval: Duration,
+    tx: mpsc::Sender<FeedUpdate>,
+) {
+    let client = RpcClient::new(rpc_url);
+    let mut ticker = tokio::time::interval(poll_interval);
+
+    loop {
+        ticker.tick().await;
+        for &pool in &pools {
+            let config = RpcAccountInfoConfig { commitment: Some(CommitmentConfig::confirmed()), ..Default::default() };
+            match client.get_account_with_config(&pool, config).await {
+                Ok(response) => {
+                    let slot = response.context.slot;
+                    let Some(account) = response.value else { continue };
+                    let _ = tx
+                        .send(FeedUpdate {
+                            source: FeedSource::Rpc,
+                            pool,
+                            slot,
+                            data: account.data,
+                            arrived_at: Instant::now(),
+                        })
+                        .await;
+                }
+                Err(err) => log::warn!("price_feed: rpc poll for {pool} failed: {err}"),
+            }
+        }
+    }
+}